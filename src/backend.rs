@@ -0,0 +1,83 @@
+use std::io;
+use std::process::{Command, ExitStatus, Stdio};
+
+use itertools::Itertools;
+
+/// Runs the cargo subcommand on the remote host, however that host
+/// chooses to execute it. `--backend` selects the implementation; new
+/// backends (e.g. podman) only need to implement this trait.
+pub trait BuildBackend {
+    /// Runs `cargo_args` against the sources already synced to
+    /// `build_path` on `remote_host`, streaming output inline, and
+    /// returns the process exit status.
+    fn run(
+        &self,
+        remote_host: &str,
+        build_path: &str,
+        build_env: &[String],
+        cargo_args: &str,
+    ) -> io::Result<ExitStatus>;
+}
+
+/// Runs cargo directly in a login shell on the remote host, picking up
+/// whatever toolchain is installed there. This is the original, default
+/// behavior.
+pub struct SshBackend;
+
+impl BuildBackend for SshBackend {
+    fn run(
+        &self,
+        remote_host: &str,
+        build_path: &str,
+        build_env: &[String],
+        cargo_args: &str,
+    ) -> io::Result<ExitStatus> {
+        let command = format!(
+            "cd {}; eval $(direnv export bash); {} cargo {}",
+            build_path,
+            build_env.iter().join(" "),
+            cargo_args,
+        );
+
+        run_over_ssh(remote_host, &command)
+    }
+}
+
+/// Runs cargo inside a container on the remote host, so the build uses a
+/// pinned, reproducible toolchain instead of whatever is installed on
+/// the host. The build directory is bind-mounted in, so `copy_back` and
+/// the `Cargo.lock` transfer work the same as with [`SshBackend`].
+pub struct DockerBackend {
+    pub image: String,
+}
+
+impl BuildBackend for DockerBackend {
+    fn run(
+        &self,
+        remote_host: &str,
+        build_path: &str,
+        build_env: &[String],
+        cargo_args: &str,
+    ) -> io::Result<ExitStatus> {
+        let env_flags = build_env.iter().map(|e| format!("-e {}", e)).join(" ");
+
+        let command = format!(
+            "docker run --rm -v {0}:/work -w /work {1} {2} cargo {3}",
+            build_path, env_flags, self.image, cargo_args,
+        );
+
+        run_over_ssh(remote_host, &command)
+    }
+}
+
+fn run_over_ssh(remote_host: &str, command: &str) -> io::Result<ExitStatus> {
+    Command::new("ssh")
+        .arg("-t")
+        .arg(remote_host)
+        .arg(command)
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .stdin(Stdio::inherit())
+        .output()
+        .map(|output| output.status)
+}
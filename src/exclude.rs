@@ -0,0 +1,17 @@
+/// Single source of truth for the "don't transfer this" rule applied by
+/// every path that walks or rsyncs the project tree: the `target`
+/// directory is always excluded, and dotfiles are excluded unless
+/// `--transfer-hidden` was passed.
+pub fn is_excluded(name: &str, hidden: bool) -> bool {
+    name == "target" || (!hidden && name.starts_with('.'))
+}
+
+/// The same rule expressed as rsync `--exclude` patterns, for call sites
+/// that shell out to rsync instead of walking the tree themselves.
+pub fn rsync_patterns(hidden: bool) -> Vec<&'static str> {
+    let mut patterns = vec!["target"];
+    if !hidden {
+        patterns.push(".*");
+    }
+    patterns
+}
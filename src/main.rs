@@ -10,7 +10,13 @@ use toml::Value;
 
 const PROGRESS_FLAG: &str = "--info=progress2";
 
+mod backend;
+mod exclude;
 mod patches;
+mod pool;
+mod sync;
+mod transfer;
+mod watch;
 
 /// Environment variables that are whitelisted to be forwarded from the process
 /// env to the remote cargo instance.
@@ -51,6 +57,17 @@ enum Opts {
         #[structopt(short = "r", long)]
         remote: Option<String>,
 
+        /// Pick a build server automatically from the pool of `[[remote]]`
+        /// entries in the config file, choosing whichever reachable server
+        /// currently has the lowest load relative to its core count.
+        #[structopt(short = "R", long = "remote-pool")]
+        remote_pool: bool,
+
+        /// Print the reachability and current load of every server in the
+        /// configured remote pool, then exit without building.
+        #[structopt(long = "list-servers")]
+        list_servers: bool,
+
         /// Set remote environment variables. RUST_BACKTRACE, CC, LIB, etc.
         #[structopt(short = "b", long)]
         build_env: Vec<String>,
@@ -72,10 +89,32 @@ enum Opts {
         #[structopt(short = "h", long = "transfer-hidden")]
         hidden: bool,
 
+        /// Force a full source transfer (the pre-existing `--delete` rsync
+        /// of the whole tree) instead of the default incremental sync, and
+        /// reset the sync manifest used to compute the changed set.
+        #[structopt(long)]
+        full_sync: bool,
+
         /// Ignore Cargo patches.
         #[structopt(long)]
         ignore_patches: bool,
 
+        /// Execution backend to build with. `ssh` (default) runs cargo
+        /// directly on the remote host; `docker` runs it inside a
+        /// container via `docker run` on the remote host.
+        #[structopt(long)]
+        backend: Option<String>,
+
+        /// Docker image to use when `--backend docker` is selected.
+        #[structopt(long = "docker-image")]
+        docker_image: Option<String>,
+
+        /// After the initial sync and build, watch the project directory
+        /// for source changes and re-sync and rebuild on the remote host
+        /// whenever they settle, until interrupted.
+        #[structopt(short = "w", long)]
+        watch: bool,
+
         #[structopt(flatten)]
         remote_commands: RemoteCommands,
     },
@@ -117,12 +156,18 @@ fn main() {
 
     let Opts::Remote {
         remote,
+        remote_pool,
+        list_servers,
         mut build_env,
         copy_back,
         no_copy_lock,
         manifest_path,
         hidden,
+        full_sync,
         ignore_patches,
+        backend,
+        docker_image,
+        watch,
         remote_commands,
     } = Opts::from_args();
 
@@ -163,38 +208,65 @@ fn main() {
     let build_path = format!("{}/{}/", build_path_folder, project_name.to_string_lossy());
 
     debug!("Project name: {:?}", project_name);
-    let configs = vec![
+    let configs: Vec<Value> = vec![
         config_from_file(&project_dir.join(".cargo-remote.toml")),
         xdg::BaseDirectories::with_prefix("cargo-remote")
             .ok()
             .and_then(|base| base.find_config_file("cargo-remote.toml"))
             .and_then(|p| config_from_file(&p)),
-    ];
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    if list_servers {
+        let servers = configs
+            .iter()
+            .find_map(pool::servers_from_config)
+            .unwrap_or_else(|| {
+                error!("No remote pool configured (expected a [[remote]] array in the config file)");
+                exit(-3);
+            });
+
+        println!("{}", pool::list_servers_report(&servers));
+        return;
+    }
 
     // TODO: move Opts::Remote fields into own type and implement complete_from_config(&mut self, config: &Value)
-    let build_server = remote
-        .or_else(|| {
-            configs
-                .into_iter()
-                .flat_map(|config| config.and_then(|c| c["remote"].as_str().map(String::from)))
-                .next()
-        })
-        .unwrap_or_else(|| {
-            error!("No remote build server was defined (use config file or --remote flag)");
+    let build_server = if remote_pool {
+        let servers = configs
+            .iter()
+            .find_map(pool::servers_from_config)
+            .unwrap_or_else(|| {
+                error!("No remote pool configured (expected a [[remote]] array in the config file)");
+                exit(-3);
+            });
+
+        pool::select_build_server(&servers).unwrap_or_else(|| {
+            error!("No reachable server found in the remote pool");
             exit(-3);
-        });
+        })
+    } else {
+        remote
+            .or_else(|| {
+                configs
+                    .iter()
+                    .find_map(|c| c["remote"].as_str().map(String::from))
+            })
+            .unwrap_or_else(|| {
+                error!("No remote build server was defined (use config file or --remote flag)");
+                exit(-3);
+            })
+    };
 
     debug!("Transferring sources to build server.");
     // transfer project to build server
-    copy_to_remote(
-        &format!("{}/", project_dir.display()),
-        &format!("{}:{}", build_server, build_path),
-        hidden,
-    )
-    .unwrap_or_else(|e| {
-        error!("Failed to transfer project to build server (error: {})", e);
-        exit(-4);
-    });
+    sync::sync_to_remote(&project_dir, &build_server, &build_path, hidden, full_sync).unwrap_or_else(
+        |e| {
+            error!("Failed to transfer project to build server (error: {})", e);
+            exit(-4);
+        },
+    );
 
     if !ignore_patches {
         patches::handle_patches(&build_path, &build_server, manifest_path, hidden).unwrap_or_else(
@@ -215,81 +287,78 @@ fn main() {
             .map(|(k, v)| format!(r#"{}="{}""#, k, v)),
     );
 
-    let build_command = format!(
-        "cd {}; eval $(direnv export bash); {} cargo {}",
-        build_path,
-        build_env.into_iter().join(" "),
-        remote_commands.into_commands().join(" "),
-    );
+    let backend_name = backend.or_else(|| {
+        configs
+            .iter()
+            .find_map(|c| c["backend"].as_str().map(String::from))
+    });
+    let docker_image = docker_image.or_else(|| {
+        configs
+            .iter()
+            .find_map(|c| c["docker-image"].as_str().map(String::from))
+    });
+
+    let build_backend: Box<dyn backend::BuildBackend> = match backend_name.as_deref() {
+        None | Some("ssh") => Box::new(backend::SshBackend),
+        Some("docker") => Box::new(backend::DockerBackend {
+            image: docker_image.unwrap_or_else(|| {
+                error!("--backend docker requires --docker-image (or a `docker-image` config entry)");
+                exit(-5);
+            }),
+        }),
+        Some(other) => {
+            error!("Unknown build backend '{}'", other);
+            exit(-5);
+        }
+    };
+
+    let cargo_args = remote_commands.into_commands().join(" ");
 
     debug!("Starting build process.");
-    let output = Command::new("ssh")
-        .arg("-t")
-        .arg(&build_server)
-        .arg(build_command)
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .stdin(Stdio::inherit())
-        .output()
+    let status = build_backend
+        .run(&build_server, &build_path, &build_env, &cargo_args)
         .unwrap_or_else(|e| {
             error!("Failed to run cargo command remotely (error: {})", e);
             exit(-5);
         });
 
-    if let Some(file_name) = copy_back {
-        debug!("Transferring artifacts back to client.");
-        let file_name = file_name.unwrap_or_else(String::new);
-        Command::new("rsync")
-            .arg("--links")
-            .arg("--recursive")
-            .arg("--quiet")
-            .arg("--delete")
-            .arg("--compress")
-            .arg(PROGRESS_FLAG)
-            .arg(format!(
-                "{}:{}target/{}",
-                build_server, build_path, file_name
-            ))
-            .arg(format!("{}/target/{}", project_dir.display(), file_name))
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .stdin(Stdio::inherit())
-            .output()
-            .unwrap_or_else(|e| {
-                error!(
-                    "Failed to transfer target back to local machine (error: {})",
-                    e
-                );
-                exit(-6);
-            });
+    if let Err(e) = transfer::copy_artifacts_back(&build_server, &build_path, &project_dir, &copy_back)
+    {
+        error!(
+            "Failed to transfer target back to local machine (error: {})",
+            e
+        );
+        exit(-6);
     }
 
-    if !no_copy_lock {
-        debug!("Transferring Cargo.lock file back to client.");
-        Command::new("rsync")
-            .arg("--links")
-            .arg("--recursive")
-            .arg("--quiet")
-            .arg("--delete")
-            .arg("--compress")
-            .arg(PROGRESS_FLAG)
-            .arg(format!("{}:{}/Cargo.lock", build_server, build_path))
-            .arg(format!("{}/Cargo.lock", project_dir.display()))
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .stdin(Stdio::inherit())
-            .output()
-            .unwrap_or_else(|e| {
-                error!(
-                    "Failed to transfer Cargo.lock back to local machine (error: {})",
-                    e
-                );
-                exit(-7);
-            });
+    if let Err(e) = transfer::copy_lock_back(&build_server, &build_path, &project_dir, no_copy_lock) {
+        error!(
+            "Failed to transfer Cargo.lock back to local machine (error: {})",
+            e
+        );
+        exit(-7);
+    }
+
+    if watch {
+        watch::watch_and_rebuild(
+            &project_dir,
+            &build_server,
+            &build_path,
+            hidden,
+            &build_env,
+            &cargo_args,
+            build_backend.as_ref(),
+            &copy_back,
+            no_copy_lock,
+        )
+        .unwrap_or_else(|e| {
+            error!("Failed to watch project directory (error: {})", e);
+            exit(-8);
+        });
     }
 
-    if !output.status.success() {
-        exit(output.status.code().unwrap_or(1))
+    if !status.success() {
+        exit(status.code().unwrap_or(1))
     }
 }
 
@@ -305,12 +374,10 @@ pub fn copy_to_remote(
         .arg("--quiet")
         .arg("--delete")
         .arg("--compress")
-        .arg(PROGRESS_FLAG)
-        .arg("--exclude")
-        .arg("target");
+        .arg(PROGRESS_FLAG);
 
-    if !hidden {
-        rsync_to.arg("--exclude").arg(".*");
+    for pattern in exclude::rsync_patterns(hidden) {
+        rsync_to.arg("--exclude").arg(pattern);
     }
 
     rsync_to
@@ -0,0 +1,163 @@
+use std::process::Command;
+
+use itertools::Itertools;
+use log::{debug, warn};
+use toml::Value;
+
+/// A single build server entry from a `[[remote]]` array in
+/// `.cargo-remote.toml`, as used by the `--remote-pool` scheduler.
+#[derive(Debug, Clone)]
+pub struct BuildServer {
+    pub host: String,
+    pub weight: f64,
+    pub max_jobs: Option<u32>,
+}
+
+/// The result of probing a [`BuildServer`] over SSH.
+#[derive(Debug)]
+struct ServerStatus {
+    server: BuildServer,
+    reachable: bool,
+    cores: Option<u32>,
+    load: Option<f64>,
+    running_jobs: Option<u32>,
+}
+
+impl ServerStatus {
+    /// Lower is better; unreachable servers and servers at their
+    /// configured `max_jobs` cap always sort last.
+    fn score(&self) -> f64 {
+        let at_capacity = matches!(
+            (self.server.max_jobs, self.running_jobs),
+            (Some(max), Some(running)) if running >= max
+        );
+
+        match (self.cores, self.load) {
+            (Some(cores), Some(load)) if cores > 0 && !at_capacity => {
+                (load / cores as f64) / self.server.weight.max(0.01)
+            }
+            _ => f64::INFINITY,
+        }
+    }
+}
+
+/// Parses the `[[remote]]` array of a config file into a pool of build
+/// servers. Returns [`None`] if the array is absent, empty, or malformed.
+pub fn servers_from_config(config: &Value) -> Option<Vec<BuildServer>> {
+    let entries = config.get("remote")?.as_array()?;
+
+    let servers = entries
+        .iter()
+        .filter_map(|entry| {
+            let host = entry.get("host")?.as_str()?.to_string();
+            let weight = entry
+                .get("weight")
+                .and_then(Value::as_float)
+                .unwrap_or(1.0);
+            let max_jobs = entry
+                .get("max-concurrent-jobs")
+                .and_then(Value::as_integer)
+                .map(|jobs| jobs as u32);
+
+            Some(BuildServer {
+                host,
+                weight,
+                max_jobs,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if servers.is_empty() {
+        None
+    } else {
+        Some(servers)
+    }
+}
+
+/// Probes a single server's reachability, load, and running job count
+/// over SSH: `nproc` for the core count, the 1-minute average from
+/// `/proc/loadavg`, and a count of currently running `cargo` processes
+/// to weigh against the server's configured `max_jobs`.
+fn probe(server: &BuildServer) -> ServerStatus {
+    let output = Command::new("ssh")
+        .arg(&server.host)
+        .arg("nproc; cat /proc/loadavg; pgrep -c '^cargo$' || echo 0")
+        .output();
+
+    let (cores, load, running_jobs) = match output {
+        Ok(out) if out.status.success() => {
+            let stdout = String::from_utf8_lossy(&out.stdout);
+            let mut lines = stdout.lines();
+            let cores = lines.next().and_then(|line| line.trim().parse().ok());
+            let load = lines
+                .next()
+                .and_then(|line| line.split_whitespace().next())
+                .and_then(|load| load.parse().ok());
+            let running_jobs = lines.next().and_then(|line| line.trim().parse().ok());
+            (cores, load, running_jobs)
+        }
+        Ok(out) => {
+            debug!(
+                "Probe of '{}' exited with status {}: {}",
+                server.host,
+                out.status,
+                String::from_utf8_lossy(&out.stderr)
+            );
+            (None, None, None)
+        }
+        Err(e) => {
+            debug!("Failed to probe '{}' (error: {})", server.host, e);
+            (None, None, None)
+        }
+    };
+
+    ServerStatus {
+        server: server.clone(),
+        reachable: cores.is_some() && load.is_some(),
+        cores,
+        load,
+        running_jobs,
+    }
+}
+
+/// Probes every server in the pool and picks the reachable one with the
+/// lowest weighted load, effectively falling back to the next candidate
+/// whenever a server turns out to be unreachable.
+pub fn select_build_server(servers: &[BuildServer]) -> Option<String> {
+    let mut statuses: Vec<ServerStatus> = servers.iter().map(probe).collect();
+    statuses.sort_by(|a, b| a.score().partial_cmp(&b.score()).unwrap());
+
+    let chosen = statuses.into_iter().find(|status| status.reachable);
+    if chosen.is_none() {
+        warn!("No reachable server found in the remote pool");
+    }
+
+    chosen.map(|status| status.server.host)
+}
+
+/// Probes every server in the pool and renders a human-readable report of
+/// each one's reachability and current load, for `--list-servers`.
+pub fn list_servers_report(servers: &[BuildServer]) -> String {
+    servers
+        .iter()
+        .map(probe)
+        .map(|status| {
+            if status.reachable {
+                let jobs = match (status.running_jobs, status.server.max_jobs) {
+                    (Some(running), Some(max)) => format!("{}/{} jobs", running, max),
+                    (Some(running), None) => format!("{} jobs", running),
+                    (None, _) => "jobs unknown".to_string(),
+                };
+                format!(
+                    "{}\treachable\tload {:.2}/{} cores\t{}",
+                    status.server.host,
+                    status.load.unwrap_or_default(),
+                    status.cores.unwrap_or_default(),
+                    jobs
+                )
+            } else {
+                format!("{}\tunreachable", status.server.host)
+            }
+        })
+        .join("\n")
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use itertools::Itertools;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::PROGRESS_FLAG;
+
+/// Size and modification time of a single tracked file, cheap enough to
+/// stat on every invocation without hashing file contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct FileEntry {
+    size: u64,
+    mtime: u64,
+}
+
+/// A snapshot of the project tree as it looked after the last sync,
+/// persisted under `target/.cargo-remote-sync.json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FileManifest {
+    files: HashMap<String, FileEntry>,
+}
+
+/// Transfers the project to the remote build server, either doing a full
+/// `--delete` rsync of the whole tree (when `full_sync` is set, or no
+/// manifest exists yet) or computing the changed set against the last
+/// recorded manifest and transferring only those files.
+pub fn sync_to_remote(
+    project_dir: &Path,
+    remote_host: &str,
+    remote_path: &str,
+    hidden: bool,
+    full_sync: bool,
+) -> io::Result<()> {
+    let manifest_file = manifest_path(project_dir);
+
+    if full_sync || !manifest_file.exists() {
+        let _ = fs::remove_file(&manifest_file);
+        crate::copy_to_remote(
+            &format!("{}/", project_dir.display()),
+            &format!("{}:{}", remote_host, remote_path),
+            hidden,
+        )?;
+        save_manifest(&manifest_file, &scan_project(project_dir, hidden))?;
+        return Ok(());
+    }
+
+    let previous = load_manifest(&manifest_file);
+    let current = scan_project(project_dir, hidden);
+    let (changed, deleted) = diff(&previous, &current);
+
+    if changed.is_empty() && deleted.is_empty() {
+        debug!("No source changes detected since last sync, skipping transfer.");
+        return save_manifest(&manifest_file, &current);
+    }
+
+    debug!(
+        "{} changed file(s), {} deleted file(s) since last sync.",
+        changed.len(),
+        deleted.len()
+    );
+
+    if !changed.is_empty() {
+        let files_from = write_files_from(project_dir, &changed)?;
+
+        Command::new("rsync")
+            .arg("--links")
+            .arg("--relative")
+            .arg("--quiet")
+            .arg("--compress")
+            .arg(PROGRESS_FLAG)
+            .arg("--rsync-path")
+            .arg("mkdir -p remote-builds && rsync")
+            .arg("--files-from")
+            .arg(&files_from)
+            .arg(format!("{}/", project_dir.display()))
+            .arg(format!("{}:{}", remote_host, remote_path))
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .stdin(Stdio::inherit())
+            .output()?;
+
+        let _ = fs::remove_file(&files_from);
+    }
+
+    if !deleted.is_empty() {
+        let remove_cmd = deleted
+            .iter()
+            .map(|path| shell_quote(&format!("{}{}", remote_path, path)))
+            .join(" ");
+
+        Command::new("ssh")
+            .arg(remote_host)
+            .arg(format!("rm -f -- {}", remove_cmd))
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .stdin(Stdio::inherit())
+            .output()?;
+    }
+
+    save_manifest(&manifest_file, &current)
+}
+
+fn manifest_path(project_dir: &Path) -> PathBuf {
+    project_dir.join("target").join(".cargo-remote-sync.json")
+}
+
+fn load_manifest(path: &Path) -> FileManifest {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_manifest(path: &Path, manifest: &FileManifest) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string(manifest).unwrap_or_default())
+}
+
+/// Walks the project tree, applying the same `target`/hidden-file
+/// exclusions as [`crate::copy_to_remote`], and records each file's size
+/// and modification time.
+fn scan_project(project_dir: &Path, hidden: bool) -> FileManifest {
+    let mut files = HashMap::new();
+
+    let entries = WalkDir::new(project_dir)
+        .into_iter()
+        .filter_entry(|entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            let name = entry.file_name().to_string_lossy();
+            !crate::exclude::is_excluded(&name, hidden)
+        })
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file());
+
+    for entry in entries {
+        let relative = entry
+            .path()
+            .strip_prefix(project_dir)
+            .unwrap()
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        if let Ok(metadata) = entry.metadata() {
+            let mtime = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default();
+
+            files.insert(
+                relative,
+                FileEntry {
+                    size: metadata.len(),
+                    mtime,
+                },
+            );
+        }
+    }
+
+    FileManifest { files }
+}
+
+/// Diffs two manifests, returning the paths that were added or modified
+/// and the paths that were removed.
+fn diff(previous: &FileManifest, current: &FileManifest) -> (Vec<String>, Vec<String>) {
+    let changed = current
+        .files
+        .iter()
+        .filter(|(path, entry)| previous.files.get(*path) != Some(*entry))
+        .map(|(path, _)| path.clone())
+        .collect();
+
+    let deleted = previous
+        .files
+        .keys()
+        .filter(|path| !current.files.contains_key(*path))
+        .cloned()
+        .collect();
+
+    (changed, deleted)
+}
+
+/// Single-quotes `s` for safe embedding in the remote `rm` shell command,
+/// escaping any embedded single quotes. Paths come from the local file
+/// tree, which may contain arbitrary characters, so this must not be
+/// skipped even though it looks paranoid.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Writes the changed-file list to a temporary file under `target/` for
+/// use with rsync's `--files-from`.
+fn write_files_from(project_dir: &Path, files: &[String]) -> io::Result<PathBuf> {
+    let path = project_dir.join("target").join(".cargo-remote-files-from");
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, files.join("\n"))?;
+    Ok(path)
+}
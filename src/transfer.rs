@@ -0,0 +1,99 @@
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use log::{debug, error};
+
+use crate::PROGRESS_FLAG;
+
+/// Rsyncs the build artifacts requested via `--copy-back` from the
+/// remote build server to the local project. No-op if `copy_back` is
+/// `None`.
+pub fn copy_artifacts_back(
+    build_server: &str,
+    build_path: &str,
+    project_dir: &Path,
+    copy_back: &Option<Option<String>>,
+) -> io::Result<()> {
+    let Some(file_name) = copy_back else {
+        return Ok(());
+    };
+
+    debug!("Transferring artifacts back to client.");
+    let file_name = file_name.clone().unwrap_or_default();
+    Command::new("rsync")
+        .arg("--links")
+        .arg("--recursive")
+        .arg("--quiet")
+        .arg("--delete")
+        .arg("--compress")
+        .arg(PROGRESS_FLAG)
+        .arg(format!(
+            "{}:{}target/{}",
+            build_server, build_path, file_name
+        ))
+        .arg(format!("{}/target/{}", project_dir.display(), file_name))
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .stdin(Stdio::inherit())
+        .output()?;
+
+    Ok(())
+}
+
+/// Rsyncs `Cargo.lock` back from the remote build server. No-op if
+/// `no_copy_lock` is set.
+pub fn copy_lock_back(
+    build_server: &str,
+    build_path: &str,
+    project_dir: &Path,
+    no_copy_lock: bool,
+) -> io::Result<()> {
+    if no_copy_lock {
+        return Ok(());
+    }
+
+    debug!("Transferring Cargo.lock file back to client.");
+    Command::new("rsync")
+        .arg("--links")
+        .arg("--recursive")
+        .arg("--quiet")
+        .arg("--delete")
+        .arg("--compress")
+        .arg(PROGRESS_FLAG)
+        .arg(format!("{}:{}/Cargo.lock", build_server, build_path))
+        .arg(format!("{}/Cargo.lock", project_dir.display()))
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .stdin(Stdio::inherit())
+        .output()?;
+
+    Ok(())
+}
+
+/// Runs both transfers back, logging (but not failing on) any error.
+/// Used by the `--watch` loop, which should keep watching after a single
+/// failed copy-back rather than aborting the whole session; the one-shot
+/// path in `main` calls [`copy_artifacts_back`]/[`copy_lock_back`]
+/// directly so it can exit on failure instead.
+pub fn copy_back_to_local_lenient(
+    build_server: &str,
+    build_path: &str,
+    project_dir: &Path,
+    copy_back: &Option<Option<String>>,
+    no_copy_lock: bool,
+) {
+    if let Err(e) = copy_artifacts_back(build_server, build_path, project_dir, copy_back) {
+        error!(
+            "Failed to transfer target back to local machine (error: {})",
+            e
+        );
+    }
+
+    if let Err(e) = copy_lock_back(build_server, build_path, project_dir, no_copy_lock) {
+        error!(
+            "Failed to transfer Cargo.lock back to local machine (error: {})",
+            e
+        );
+    }
+}
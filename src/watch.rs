@@ -0,0 +1,95 @@
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use log::{debug, error, info};
+use notify::{RecursiveMode, Watcher};
+
+use crate::backend::BuildBackend;
+use crate::sync;
+
+/// How long to wait for further events after the first one before
+/// actually re-syncing, so a burst of saves only triggers one rebuild.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Watches `project_dir` for source changes and, whenever one settles
+/// after a short debounce window, re-syncs the changed files and re-runs
+/// the cargo command on the remote host. The watcher is held for the
+/// whole loop, so this blocks until the process is interrupted (e.g. by
+/// Ctrl-C).
+pub fn watch_and_rebuild(
+    project_dir: &Path,
+    remote_host: &str,
+    build_path: &str,
+    hidden: bool,
+    build_env: &[String],
+    cargo_args: &str,
+    backend: &dyn BuildBackend,
+    copy_back: &Option<Option<String>>,
+    no_copy_lock: bool,
+) -> notify::Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(project_dir, RecursiveMode::Recursive)?;
+
+    info!(
+        "Watching '{}' for changes (Ctrl-C to stop).",
+        project_dir.display()
+    );
+
+    while let Ok(event) = rx.recv() {
+        if !is_relevant(&event, project_dir, hidden) {
+            continue;
+        }
+
+        // Drain and debounce further events so a burst of saves only
+        // triggers a single rebuild.
+        while rx.recv_timeout(DEBOUNCE).is_ok() {}
+
+        debug!("Source change detected, re-syncing and rebuilding.");
+
+        if let Err(e) = sync::sync_to_remote(project_dir, remote_host, build_path, hidden, false) {
+            error!("Failed to sync changes to build server (error: {})", e);
+            continue;
+        }
+
+        match backend.run(remote_host, build_path, build_env, cargo_args) {
+            Ok(status) if status.success() => {
+                crate::transfer::copy_back_to_local_lenient(
+                    remote_host,
+                    build_path,
+                    project_dir,
+                    copy_back,
+                    no_copy_lock,
+                );
+            }
+            Ok(status) => {
+                error!("Remote build exited with status {}", status);
+            }
+            Err(e) => error!("Failed to run cargo command remotely (error: {})", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a notify event touches a path we care about, applying the
+/// same `target`/hidden-file exclusions as [`crate::copy_to_remote`].
+fn is_relevant(event: &notify::Result<notify::Event>, project_dir: &Path, hidden: bool) -> bool {
+    let event = match event {
+        Ok(event) => event,
+        Err(_) => return false,
+    };
+
+    event.paths.iter().any(|path| {
+        let relative = match path.strip_prefix(project_dir) {
+            Ok(relative) => relative,
+            Err(_) => return false,
+        };
+
+        !relative.components().any(|component| {
+            let name = component.as_os_str().to_string_lossy();
+            crate::exclude::is_excluded(&name, hidden)
+        })
+    })
+}